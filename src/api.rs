@@ -7,82 +7,355 @@
 //! `ghprod` uses a model that frontloads the actual data retrieval from the
 //! GitHub API in an effort to minimise requests. As a result, all queries we
 //! perform act on already-retrieved data.
-use std::sync::Arc;
+//!
+//! Retrieval itself goes over GitHub's GraphQL API rather than the REST API:
+//! the REST `pulls().list()` endpoint doesn't populate `additions`/
+//! `deletions` on list responses, which made [`pull_request_net_change`]
+//! silently useless. A single GraphQL query per page gets us everything
+//! `metrics` needs in one round trip.
+//!
+//! Pagination itself is adaptive: rather than a fixed sleep between pages, we
+//! read GitHub's `X-RateLimit-Remaining`/`X-RateLimit-Reset` response headers
+//! and only sleep the minimum needed to avoid exhausting the budget.
+//!
+//! We don't attempt `ETag`/`If-None-Match` conditional requests here: that
+//! works against GitHub's REST API, but the GraphQL endpoint (`POST
+//! /graphql`) doesn't emit `ETag` headers or honour conditional requests, so
+//! there's nothing to cache against. An earlier version of this module
+//! shipped that caching anyway, keyed by owner/repo/cursor -- it just never
+//! hit, since `cache.insert` was never reachable. Dropped rather than kept
+//! as dead weight; revisit if a REST fallback path is ever added.
+use std::{sync::Arc, time::Duration};
 
+use chrono::{DateTime, TimeZone, Utc};
+use graphql_client::GraphQLQuery;
+use indicatif::{ProgressBar, ProgressStyle};
 use log::{debug, info, warn};
-use octocrab::{models::pulls::PullRequest, Octocrab};
+use octocrab::Octocrab;
 
-use crate::error::GhProdError;
+use crate::error::{GhProdError, ERROR_GRAPHQL_RESPONSE};
 
 pub const MILLISECONDS_PER_SECOND: u64 = 1000;
 pub const SECONDS_PER_MINUTE: u64 = 60;
 
-/// Number of milliseconds to sleep for after each page is requested from the GitHub API.
-///
-/// The current rate limits for *unauthenticated* requests are 60 requests per
-/// hour (obviously, this is one request per minute on average). Source:
-/// [https://docs.github.com/en/rest/overview/resources-in-the-rest-api?apiVersion=2022-11-28#rate-limits-for-requests-from-personal-accounts](https://docs.github.com/en/rest/overview/resources-in-the-rest-api?apiVersion=2022-11-28#rate-limits-for-requests-from-personal-accounts)
+/// Fallback number of milliseconds to sleep between pages when GitHub's
+/// rate-limit headers are unavailable for some reason.
 pub const SLEEP_DURATION_MILLIS: u64 = MILLISECONDS_PER_SECOND * SECONDS_PER_MINUTE;
 
+/// Remaining-request floor at or below which we stop firing eagerly and instead
+/// wait out the rate-limit window before the next page.
+pub const RATE_LIMIT_FLOOR: u32 = 5;
+
 /// Maximum number of items to receive from the GitHub API per page.
 ///
 /// The current maximum is 100.
 pub const MAX_NUM_PER_PAGE: u8 = 100;
 
-/// Returns all pull requests for the given repository
+/// An opaque GraphQL pagination cursor, as returned in `pageInfo.endCursor`.
+pub type Cursor = String;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/github_schema.graphql",
+    query_path = "graphql/pull_requests.graphql",
+    response_derives = "Debug"
+)]
+pub struct PullRequestsQuery;
+
+/// A pull request as retrieved from the GraphQL API.
+///
+/// This carries the subset of `octocrab::models::pulls::PullRequest` that
+/// `metrics` actually consumes. Unlike the REST list endpoint, `additions`
+/// and `deletions` are always populated here.
+#[derive(Clone, Debug)]
+pub struct PrRecord {
+    pub author: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub merged_at: Option<DateTime<Utc>>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub additions: Option<i64>,
+    pub deletions: Option<i64>,
+}
+
+/// A single step of a GraphQL cursor-paginated query.
+///
+/// Implementors describe how to seed the `after` cursor on their generated
+/// `Variables` type, and how to pull `Item`s plus the next page's cursor out
+/// of a successful response. [`fetch_all_pull_requests`] (and future
+/// GraphQL-backed fetchers) drive this until GitHub reports no more pages.
+pub trait ChunkedQuery: GraphQLQuery {
+    type Item;
+
+    /// Returns `vars` with the `after` cursor set to `cursor`.
+    fn set_after(&self, vars: Self::Variables, cursor: Option<Cursor>) -> Self::Variables;
+
+    /// Extracts this page's items and the next page's cursor (if any) from `data`.
+    fn process(
+        &self,
+        data: Self::ResponseData,
+    ) -> Result<(Vec<Self::Item>, Option<Cursor>), GhProdError>;
+}
+
+impl ChunkedQuery for PullRequestsQuery {
+    type Item = PrRecord;
+
+    fn set_after(
+        &self,
+        mut vars: pull_requests_query::Variables,
+        cursor: Option<Cursor>,
+    ) -> pull_requests_query::Variables {
+        vars.after = cursor;
+        vars
+    }
+
+    fn process(
+        &self,
+        data: pull_requests_query::ResponseData,
+    ) -> Result<(Vec<Self::Item>, Option<Cursor>), GhProdError> {
+        let pull_requests = data
+            .repository
+            .ok_or_else(|| {
+                GhProdError::new(
+                    ERROR_GRAPHQL_RESPONSE,
+                    "Response was missing `repository`".to_string(),
+                    None,
+                )
+            })?
+            .pull_requests;
+
+        let items = pull_requests
+            .nodes
+            .unwrap_or_default()
+            .into_iter()
+            .flatten()
+            .map(|node| PrRecord {
+                author: node.author.map(|actor| actor.login),
+                created_at: Some(node.created_at),
+                merged_at: node.merged_at,
+                closed_at: node.closed_at,
+                additions: Some(node.additions),
+                deletions: Some(node.deletions),
+            })
+            .collect();
+
+        let next_cursor = if pull_requests.page_info.has_next_page {
+            pull_requests.page_info.end_cursor
+        } else {
+            None
+        };
+
+        Ok((items, next_cursor))
+    }
+}
+
+/// GitHub's rate-limit budget as reported on the most recent response.
+#[derive(Clone, Copy, Debug)]
+pub struct RateLimitStatus {
+    pub remaining: u32,
+    pub reset_at: DateTime<Utc>,
+}
+
+/// Reads `X-RateLimit-Remaining`/`X-RateLimit-Reset` off `headers`, if present.
+fn parse_rate_limit(headers: &reqwest::header::HeaderMap) -> Option<RateLimitStatus> {
+    let remaining: u32 = headers
+        .get("x-ratelimit-remaining")?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()?;
+    let reset_epoch: i64 = headers
+        .get("x-ratelimit-reset")?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()?;
+
+    Some(RateLimitStatus {
+        remaining,
+        reset_at: Utc.timestamp_opt(reset_epoch, 0).single()?,
+    })
+}
+
+/// Returns how long to sleep before the next page given `status`: zero while
+/// budget is ample, otherwise the time remaining until the window resets.
+fn rate_limit_sleep_duration(status: &RateLimitStatus) -> Duration {
+    if status.remaining > RATE_LIMIT_FLOOR {
+        return Duration::ZERO;
+    }
+
+    (status.reset_at - Utc::now())
+        .to_std()
+        .unwrap_or(Duration::ZERO)
+}
+
+/// POSTs `request_body` to the GraphQL endpoint, returning the raw response
+/// body and whatever rate-limit budget GitHub reported on the response.
+///
+/// Bypasses `Octocrab::graphql`'s convenience deserialisation so we can get
+/// at the response headers.
+async fn post_graphql(
+    client: &Octocrab,
+    request_body: &impl serde::Serialize,
+) -> Result<(String, Option<RateLimitStatus>), GhProdError> {
+    let mut request = client.request_builder(
+        client.absolute_url("graphql")?,
+        reqwest::Method::POST,
+    );
+    request = request.json(request_body);
+
+    let response = client.execute(request.build().map_err(|_| {
+        GhProdError::new(
+            ERROR_GRAPHQL_RESPONSE,
+            "Failed to build GraphQL request".to_string(),
+            None,
+        )
+    })?).await?;
+
+    let rate_limit = parse_rate_limit(response.headers());
+    let body = response.text().await?;
+
+    Ok((body, rate_limit))
+}
+
+/// Builds the progress bar shown while paginating, or `None` when `enabled`
+/// is `false` -- the caller is expected to also gate this on the process's
+/// stdout actually being a TTY so piped/JSON output isn't corrupted.
+fn build_progress_bar(enabled: bool) -> Option<ProgressBar> {
+    if !enabled {
+        return None;
+    }
+
+    let bar = ProgressBar::new_spinner();
+    bar.set_style(
+        ProgressStyle::with_template("{spinner} {msg}")
+            .expect("static progress bar template is always valid"),
+    );
+    bar.enable_steady_tick(Duration::from_millis(100));
+
+    Some(bar)
+}
+
+/// Returns all pull requests for the given repository.
+///
+/// `show_progress` renders a spinner tracking pages fetched, PRs
+/// accumulated, and any rate-limit sleep; callers should pass `false` when
+/// stdout isn't a TTY so piped/JSON output isn't corrupted.
 pub async fn fetch_all_pull_requests(
     owner: &str,
     repo: &str,
     client: Arc<Octocrab>,
-) -> Result<Vec<PullRequest>, GhProdError> {
-    let mut prs: Vec<Vec<PullRequest>> = vec![];
+    show_progress: bool,
+) -> Result<Vec<PrRecord>, GhProdError> {
+    let query = PullRequestsQuery;
+    let mut prs: Vec<PrRecord> = vec![];
+    let mut after: Option<Cursor> = None;
     let mut num_pages: usize = 0;
-    let mut page = client
-        .pulls(owner, repo)
-        .list()
-        .state(octocrab::params::State::All)
-        .per_page(MAX_NUM_PER_PAGE)
-        .send()
-        .await?;
-    prs.push(page.items.clone());
+    let progress = build_progress_bar(show_progress);
 
     loop {
         info!("Fetching page {}...", num_pages);
         num_pages += 1;
 
-        match client
-            .get_page::<octocrab::models::pulls::PullRequest>(&page.next)
-            .await?
-        {
-            Some(next_page) => {
-                if next_page.items.is_empty() {
-                    warn!("Received empty page");
-                }
-                prs.push(next_page.items.clone());
-                page = next_page;
-            }
+        let vars = query.set_after(
+            pull_requests_query::Variables {
+                owner: owner.to_string(),
+                repo: repo.to_string(),
+                after: after.clone(),
+            },
+            after.clone(),
+        );
+        let request_body = PullRequestsQuery::build_query(vars);
+
+        let (body, rate_limit) = post_graphql(&client, &request_body).await?;
+
+        let response: graphql_client::Response<pull_requests_query::ResponseData> =
+            serde_json::from_str(&body).map_err(|e| {
+                GhProdError::new(
+                    ERROR_GRAPHQL_RESPONSE,
+                    format!("Failed to parse GraphQL response: {}", e),
+                    None,
+                )
+            })?;
+
+        if let Some(errors) = response.errors.filter(|errors| !errors.is_empty()) {
+            return Err(GhProdError::new(
+                ERROR_GRAPHQL_RESPONSE,
+                format!("GraphQL query returned errors: {:?}", errors),
+                None,
+            ));
+        }
+
+        let data = response.data.ok_or_else(|| {
+            GhProdError::new(
+                ERROR_GRAPHQL_RESPONSE,
+                "GraphQL response had no data".to_string(),
+                None,
+            )
+        })?;
+
+        let total_count = data
+            .repository
+            .as_ref()
+            .map(|repository| repository.pull_requests.total_count as u64);
+
+        let (mut page_items, next_cursor) = query.process(data)?;
+        if page_items.is_empty() {
+            warn!("Received empty page");
+        }
+        prs.append(&mut page_items);
+
+        if let Some(bar) = &progress {
+            bar.set_message(match total_count {
+                Some(total) => format!("{} / {} PRs fetched ({} pages)", prs.len(), total, num_pages),
+                None => format!("{} PRs fetched ({} pages)", prs.len(), num_pages),
+            });
+        }
+
+        match next_cursor {
+            Some(cursor) => after = Some(cursor),
             None => break,
         }
 
-        debug!("Sleeping for {} milliseconds...", SLEEP_DURATION_MILLIS);
-        tokio::time::sleep(tokio::time::Duration::from_millis(SLEEP_DURATION_MILLIS)).await;
+        let sleep_duration = rate_limit
+            .map(|status| rate_limit_sleep_duration(&status))
+            .unwrap_or(Duration::from_millis(SLEEP_DURATION_MILLIS));
+
+        if sleep_duration.is_zero() {
+            debug!("Rate limit budget is ample; not sleeping before the next page");
+        } else if let Some(bar) = &progress {
+            let mut remaining = sleep_duration;
+            while remaining > Duration::ZERO {
+                bar.set_message(format!(
+                    "rate limited, resuming in {}s...",
+                    remaining.as_secs().max(1)
+                ));
+                let tick = Duration::from_secs(1).min(remaining);
+                tokio::time::sleep(tick).await;
+                remaining = remaining.saturating_sub(tick);
+            }
+        } else {
+            debug!("Sleeping for {:?} before the next page...", sleep_duration);
+            tokio::time::sleep(sleep_duration).await;
+        }
     }
 
-    info!("Retrieved {} PRs", prs.iter().flatten().count());
+    if let Some(bar) = &progress {
+        bar.finish_with_message(format!("Retrieved {} PRs across {} pages", prs.len(), num_pages));
+    }
+    info!("Retrieved {} PRs", prs.len());
 
-    Ok(prs.iter().flatten().cloned().collect())
+    Ok(prs)
 }
 
 /// Returns the net change of `pull_request`.
 ///
 /// The net change of a PR is defined as the number of lines added subtract the
 /// number of lines removed.
-#[allow(dead_code)]
-pub fn pull_request_net_change(pull_request: &PullRequest) -> Option<i64> {
+pub fn pull_request_net_change(pull_request: &PrRecord) -> Option<i64> {
     match (pull_request.additions, pull_request.deletions) {
-        (Some(a), Some(d)) => Some(a as i64 - d as i64),
-        (Some(a), None) => Some(a as i64),
-        (None, Some(d)) => Some(0 - d as i64),
+        (Some(a), Some(d)) => Some(a - d),
+        (Some(a), None) => Some(a),
+        (None, Some(d)) => Some(0 - d),
         _ => None,
     }
 }
@@ -1,3 +1,5 @@
+use std::str::FromStr;
+
 use clap::{Args, Parser, Subcommand};
 
 use crate::metrics::{Metric, PullRequestTerminatingState};
@@ -13,17 +15,71 @@ pub struct Opts {
     #[clap(short, long)]
     pub pull_request_terminating_state: Option<PullRequestTerminatingState>,
 
+    /// Output format for reports: `plain` (default prose), `table`, or `json`.
+    #[clap(short, long, default_value = "plain")]
+    pub format: OutputFormat,
+
+    /// Disable the progress bar shown while fetching paginated PR data.
+    ///
+    /// Always disabled automatically when stdout isn't a TTY (e.g. when
+    /// piping `--format json` output into another tool).
+    #[clap(long)]
+    pub no_progress: bool,
+
+    /// API key for `--narrative` mode's LLM backend. Falls back to
+    /// `GHPROD_NARRATIVE_API_KEY` when unset. Only used with the `narrative`
+    /// feature enabled.
+    #[clap(long, env = "GHPROD_NARRATIVE_API_KEY")]
+    pub narrative_api_key: Option<String>,
+
     #[clap(subcommand)]
     pub command: Commands,
 }
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OutputFormat {
+    Plain,
+    Table,
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plain" => Ok(Self::Plain),
+            "table" => Ok(Self::Table),
+            "json" => Ok(Self::Json),
+            _ => Err("Unknown output format"),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Subcommand)]
 pub enum Commands {
     Solo(SoloOpts),
+    Team(TeamOpts),
 }
 
 #[derive(Args, Clone, Debug)]
 pub struct SoloOpts {
     pub user: String,
     pub metric: Option<Metric>,
+
+    /// Replace the plain-text report with a short LLM-generated narrative
+    /// summary of the contributor's metrics. Requires the `narrative`
+    /// feature and an API key (see `--narrative-api-key`); falls back to the
+    /// plain report if either is missing or the request fails.
+    #[clap(long)]
+    pub narrative: bool,
+
+    /// Print an ASCII histogram of the user's PR durations alongside the
+    /// plain-text report.
+    #[clap(long)]
+    pub histogram: bool,
 }
+
+/// A leaderboard of every contributor's metrics, ranked by PR count.
+#[derive(Args, Clone, Debug)]
+pub struct TeamOpts {}
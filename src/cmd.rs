@@ -1,68 +1,25 @@
-use std::sync::Arc;
+use std::{io::IsTerminal, sync::Arc};
 
-use log::{debug, info, warn};
-use octocrab::{models::pulls::PullRequest, Octocrab};
+use octocrab::Octocrab;
 
 use crate::{
-    cli::{Opts, SoloOpts},
+    api::{fetch_all_pull_requests, PrRecord},
+    cli::{Opts, OutputFormat, SoloOpts, TeamOpts},
     error::GhProdError,
     metrics::{
-        mean_net_change, mean_pr_duration, median_pr_duration, pull_requests_by_author, Metric,
-        PullRequestTerminatingState,
+        mean_net_change, mean_pr_duration, median_pr_duration, p50_pr_duration, p90_pr_duration,
+        p99_pr_duration, pull_requests_by_author, total_pull_requests, DurationHistogram, Metric,
+        PullRequestTerminatingState, DEFAULT_HISTOGRAM_BUCKET_WIDTH_DAYS,
+        DEFAULT_HISTOGRAM_NUM_BUCKETS,
     },
+    output::{render_leaderboard, render_metric, ContributorMetrics},
 };
 
-pub const SLEEP_DURATION_MILLIS: u64 = 10;
-pub const MAX_NUM_PER_PAGE: u8 = 100;
-
-pub async fn fetch_all_pull_requests(
-    owner: &str,
-    repo: &str,
-    client: Arc<Octocrab>,
-) -> Result<Vec<PullRequest>, GhProdError> {
-    let mut prs: Vec<Vec<PullRequest>> = vec![];
-    let mut num_pages: usize = 0;
-    let mut page = client
-        .pulls(owner, repo)
-        .list()
-        .state(octocrab::params::State::All)
-        .per_page(MAX_NUM_PER_PAGE)
-        .send()
-        .await?;
-    prs.push(page.items.clone());
-
-    loop {
-        info!("Fetching page {}...", num_pages);
-        num_pages += 1;
-
-        match client
-            .get_page::<octocrab::models::pulls::PullRequest>(&page.next)
-            .await?
-        {
-            Some(next_page) => {
-                if next_page.items.is_empty() {
-                    warn!("Received empty page");
-                }
-                prs.push(next_page.items.clone());
-                page = next_page;
-            }
-            None => break,
-        }
-
-        debug!("Sleeping for {} milliseconds...", SLEEP_DURATION_MILLIS);
-        tokio::time::sleep(tokio::time::Duration::from_millis(SLEEP_DURATION_MILLIS)).await;
-    }
-
-    info!("Retrieved {} PRs", prs.len());
-
-    Ok(prs.iter().flatten().cloned().collect())
-}
-
 pub fn user_summary(
     owner: &str,
     repo: &str,
     user: &str,
-    pull_requests: &Vec<PullRequest>,
+    pull_requests: &Vec<PrRecord>,
     terminal_state: PullRequestTerminatingState,
 ) -> String {
     let mut report: String = String::new();
@@ -107,6 +64,58 @@ pub fn user_summary(
     report
 }
 
+/// Whether the fetch progress bar should be shown: on unless `--no-progress`
+/// is passed, and only ever when stdout is actually a TTY so piped or
+/// `--format json` output isn't corrupted by spinner frames.
+fn show_progress(global_opts: &Opts) -> bool {
+    !global_opts.no_progress && std::io::stdout().is_terminal()
+}
+
+/// Requests a narrative summary for `user` (the caller checks `--narrative`),
+/// returning `None` (the plain report) when the `narrative` feature is
+/// disabled, no API key is configured, or the request fails.
+#[cfg(feature = "narrative")]
+async fn narrative_for(
+    user: &str,
+    contributor: &ContributorMetrics,
+    global_opts: &Opts,
+) -> Option<String> {
+    let api_key = global_opts.narrative_api_key.as_deref()?;
+    crate::narrative::narrative_summary(
+        user,
+        contributor,
+        crate::narrative::DEFAULT_NARRATIVE_ENDPOINT,
+        api_key,
+    )
+    .await
+}
+
+#[cfg(not(feature = "narrative"))]
+async fn narrative_for(
+    _user: &str,
+    _contributor: &ContributorMetrics,
+    _global_opts: &Opts,
+) -> Option<String> {
+    None
+}
+
+/// Computes every tracked metric for `user`'s PRs, ready to render in any format.
+fn contributor_metrics(
+    user: &str,
+    pull_requests: &Vec<PrRecord>,
+    terminal_state: PullRequestTerminatingState,
+) -> ContributorMetrics {
+    ContributorMetrics {
+        user: user.to_string(),
+        num_prs: total_pull_requests(user, pull_requests),
+        mean_pr_duration: mean_pr_duration(user, pull_requests, terminal_state),
+        median_pr_duration: median_pr_duration(user, pull_requests, terminal_state),
+        p90_pr_duration: p90_pr_duration(user, pull_requests, terminal_state),
+        p99_pr_duration: p99_pr_duration(user, pull_requests, terminal_state),
+        mean_net_change: mean_net_change(user, pull_requests, terminal_state),
+    }
+}
+
 pub async fn solo(
     opts: SoloOpts,
     global_opts: Opts,
@@ -115,8 +124,10 @@ pub async fn solo(
     let owner: String = global_opts.owner;
     let repo: String = global_opts.repo;
     let user: String = opts.user;
-    let prs: Vec<PullRequest> =
-        fetch_all_pull_requests(owner.as_str(), repo.as_str(), client).await?;
+    let format: OutputFormat = global_opts.format;
+    let progress: bool = show_progress(&global_opts);
+    let prs: Vec<PrRecord> =
+        fetch_all_pull_requests(owner.as_str(), repo.as_str(), client, progress).await?;
 
     let pr_terminal_state: PullRequestTerminatingState =
         if let Some(t) = global_opts.pull_request_terminating_state {
@@ -126,33 +137,107 @@ pub async fn solo(
         };
 
     if let Some(metric) = opts.metric {
-        match metric {
-            Metric::MeanPrDuration => {
-                match mean_pr_duration(user.as_str(), &prs, pr_terminal_state) {
-                    Some(mean_duration) => println!("{}", mean_duration),
-                    None => println!("(null)"),
-                }
-            }
-            Metric::MedianPrDuration => {
-                match median_pr_duration(user.as_str(), &prs, pr_terminal_state) {
-                    Some(median_duration) => println!("{}", median_duration),
-                    None => println!("(null)"),
-                }
-            }
-            Metric::MeanNetChange => unimplemented!(),
+        let (metric_name, value) = match metric {
+            Metric::MeanPrDuration => (
+                "mean_pr_duration",
+                mean_pr_duration(user.as_str(), &prs, pr_terminal_state),
+            ),
+            Metric::MedianPrDuration => (
+                "median_pr_duration",
+                median_pr_duration(user.as_str(), &prs, pr_terminal_state),
+            ),
+            Metric::MeanNetChange => (
+                "mean_net_change",
+                mean_net_change(user.as_str(), &prs, pr_terminal_state),
+            ),
+            Metric::TotalPullRequests => (
+                "total_num_prs",
+                Some(total_pull_requests(user.as_str(), &prs) as f64),
+            ),
+            Metric::P50PrDuration => (
+                "p50_pr_duration",
+                p50_pr_duration(user.as_str(), &prs, pr_terminal_state),
+            ),
+            Metric::P90PrDuration => (
+                "p90_pr_duration",
+                p90_pr_duration(user.as_str(), &prs, pr_terminal_state),
+            ),
+            Metric::P99PrDuration => (
+                "p99_pr_duration",
+                p99_pr_duration(user.as_str(), &prs, pr_terminal_state),
+            ),
+        };
+        render_metric(metric_name, value, format);
+    } else if format == OutputFormat::Plain {
+        let narrative = if opts.narrative {
+            let contributor = contributor_metrics(user.as_str(), &prs, pr_terminal_state);
+            narrative_for(user.as_str(), &contributor, &global_opts).await
+        } else {
+            None
+        };
+
+        match narrative {
+            Some(summary) => println!("{}", summary),
+            None => println!(
+                "{}",
+                user_summary(
+                    owner.as_str(),
+                    repo.as_str(),
+                    user.as_str(),
+                    &prs,
+                    pr_terminal_state
+                )
+            ),
         }
-    } else {
-        println!(
-            "{}",
-            user_summary(
-                owner.as_str(),
-                repo.as_str(),
+
+        if opts.histogram {
+            let histogram = DurationHistogram::new(
                 user.as_str(),
                 &prs,
-                pr_terminal_state
-            )
-        );
+                pr_terminal_state,
+                DEFAULT_HISTOGRAM_BUCKET_WIDTH_DAYS,
+                DEFAULT_HISTOGRAM_NUM_BUCKETS,
+            );
+            println!("{}", histogram.render_ascii());
+        }
+    } else {
+        let contributor = contributor_metrics(user.as_str(), &prs, pr_terminal_state);
+        render_leaderboard(&[contributor], format);
     }
 
     Ok(())
 }
+
+pub async fn team(
+    _opts: TeamOpts,
+    global_opts: Opts,
+    client: Arc<Octocrab>,
+) -> Result<(), GhProdError> {
+    let owner: String = global_opts.owner;
+    let repo: String = global_opts.repo;
+    let format: OutputFormat = global_opts.format;
+    let progress: bool = show_progress(&global_opts);
+    let prs: Vec<PrRecord> =
+        fetch_all_pull_requests(owner.as_str(), repo.as_str(), client, progress).await?;
+
+    let pr_terminal_state: PullRequestTerminatingState =
+        if let Some(t) = global_opts.pull_request_terminating_state {
+            t
+        } else {
+            PullRequestTerminatingState::default()
+        };
+
+    let mut authors: Vec<String> = prs.iter().filter_map(|pr| pr.author.clone()).collect();
+    authors.sort();
+    authors.dedup();
+
+    let mut leaderboard: Vec<ContributorMetrics> = authors
+        .iter()
+        .map(|author| contributor_metrics(author, &prs, pr_terminal_state))
+        .collect();
+    leaderboard.sort_by(|a, b| b.num_prs.cmp(&a.num_prs));
+
+    render_leaderboard(&leaderboard, format);
+
+    Ok(())
+}
@@ -3,16 +3,24 @@ use std::{error::Error, fmt};
 use ethers::prelude::ProviderError; /* TODO: remove */
 
 pub const ERROR_CLIENT_INIT: u8 = 1u8;
+pub const ERROR_API: u8 = 2u8;
+pub const ERROR_GRAPHQL_RESPONSE: u8 = 3u8;
 
 #[derive(Debug)]
 pub enum InnerGhProdError {
     ClientError(ProviderError),
+    ApiError(octocrab::Error),
+    HttpError(reqwest::Error),
+    GraphQlResponseError(String),
 }
 
 impl fmt::Display for InnerGhProdError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Self::ClientError(e) => write!(f, "ClientError: {}", e),
+            Self::ApiError(e) => write!(f, "ApiError: {}", e),
+            Self::HttpError(e) => write!(f, "HttpError: {}", e),
+            Self::GraphQlResponseError(e) => write!(f, "GraphQlResponseError: {}", e),
         }
     }
 }
@@ -56,3 +64,23 @@ impl From<ProviderError> for GhProdError {
         )
     }
 }
+
+impl From<octocrab::Error> for GhProdError {
+    fn from(value: octocrab::Error) -> Self {
+        Self::new(
+            ERROR_API,
+            "GitHub API request failed".to_string(),
+            Some(InnerGhProdError::ApiError(value)),
+        )
+    }
+}
+
+impl From<reqwest::Error> for GhProdError {
+    fn from(value: reqwest::Error) -> Self {
+        Self::new(
+            ERROR_API,
+            "GitHub API request failed".to_string(),
+            Some(InnerGhProdError::HttpError(value)),
+        )
+    }
+}
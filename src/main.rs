@@ -14,6 +14,9 @@ mod cli;
 mod cmd;
 mod error;
 mod metrics;
+#[cfg(feature = "narrative")]
+mod narrative;
+mod output;
 
 #[tokio::main]
 async fn main() -> Result<(), GhProdError> {
@@ -29,6 +32,7 @@ async fn main() -> Result<(), GhProdError> {
 
     match opts.clone().command {
         Commands::Solo(solo_opts) => cmd::solo(solo_opts, opts, client).await?,
+        Commands::Team(team_opts) => cmd::team(team_opts, opts, client).await?,
     };
 
     info!("Initialised!");
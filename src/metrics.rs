@@ -2,9 +2,8 @@
 use std::str::FromStr;
 
 use chrono::{DateTime, Duration, Utc};
-use octocrab::models::pulls::PullRequest;
 
-use crate::api::pull_request_net_change;
+use crate::api::{pull_request_net_change, PrRecord};
 
 /// Represents a particular statistic
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -13,6 +12,9 @@ pub enum Metric {
     MedianPrDuration,
     MeanNetChange,
     TotalPullRequests,
+    P50PrDuration,
+    P90PrDuration,
+    P99PrDuration,
 }
 
 impl FromStr for Metric {
@@ -24,6 +26,9 @@ impl FromStr for Metric {
             "median_pr_duration" => Ok(Self::MedianPrDuration),
             "mean_net_change" => Ok(Self::MeanNetChange),
             "total_num_prs" => Ok(Self::TotalPullRequests),
+            "p50_pr_duration" => Ok(Self::P50PrDuration),
+            "p90_pr_duration" => Ok(Self::P90PrDuration),
+            "p99_pr_duration" => Ok(Self::P99PrDuration),
             _ => Err("Unknown metric"),
         }
     }
@@ -65,7 +70,7 @@ impl FromStr for PullRequestTerminatingState {
 
 /// Determines whether `pull_request` has terminated (based on `terminal_state`)
 pub fn pull_request_terminated(
-    pull_request: &PullRequest,
+    pull_request: &PrRecord,
     terminal_state: PullRequestTerminatingState,
 ) -> bool {
     match terminal_state {
@@ -78,7 +83,7 @@ pub fn pull_request_terminated(
 ///
 /// "Termination" here is defined by `pull_request_terminated`.
 pub fn pull_request_duration(
-    pull_request: PullRequest,
+    pull_request: PrRecord,
     terminal_state: PullRequestTerminatingState,
 ) -> Duration {
     let start_time: DateTime<Utc> = pull_request.created_at.unwrap();
@@ -95,19 +100,19 @@ pub fn pull_request_duration(
 }
 
 /// Returns the subset of PRs that are authored by `author`
-pub fn pull_requests_by_author(author: &str, pull_requests: &Vec<PullRequest>) -> Vec<PullRequest> {
+pub fn pull_requests_by_author(author: &str, pull_requests: &Vec<PrRecord>) -> Vec<PrRecord> {
     pull_requests
         .iter()
-        .filter(|pr| pr.user.as_ref().is_some_and(|user| user.login == author))
+        .filter(|pr| pr.author.as_deref() == Some(author))
         .cloned()
         .collect()
 }
 
 /// Returns the subset of PRs that have terminated
 pub fn terminated_pull_requests(
-    pull_requests: &Vec<PullRequest>,
+    pull_requests: &Vec<PrRecord>,
     terminal_state: PullRequestTerminatingState,
-) -> Vec<PullRequest> {
+) -> Vec<PrRecord> {
     pull_requests
         .iter()
         .filter(|pr| pull_request_terminated(pr, terminal_state))
@@ -123,10 +128,10 @@ pub const SECONDS_PER_DAY: u64 = 60 * 60 * 24;
 /// part of this calculation.
 pub fn mean_pr_duration(
     user: &str,
-    pull_requests: &Vec<PullRequest>,
+    pull_requests: &Vec<PrRecord>,
     terminal_state: PullRequestTerminatingState,
 ) -> Option<f64> {
-    let users_prs: Vec<PullRequest> = pull_requests_by_author(user, &pull_requests);
+    let users_prs: Vec<PrRecord> = pull_requests_by_author(user, &pull_requests);
 
     if users_prs.is_empty() {
         None
@@ -144,32 +149,178 @@ pub fn mean_pr_duration(
     }
 }
 
-/// Returns the median number of days a PR takes to terminate.
+/// Returns the PR durations (in days) for `user`, sorted ascending.
 ///
 /// Note that non-terminated PRs (i.e., PRs that are still open) are ignored as
 /// part of this calculation.
-pub fn median_pr_duration(
+fn sorted_pr_durations(
     user: &str,
-    pull_requests: &Vec<PullRequest>,
+    pull_requests: &Vec<PrRecord>,
     terminal_state: PullRequestTerminatingState,
-) -> Option<f64> {
-    let durations: Vec<f64> = pull_requests_by_author(user, &pull_requests)
-        .iter()
-        .cloned()
+) -> Vec<f64> {
+    let mut durations: Vec<f64> = pull_requests_by_author(user, &pull_requests)
+        .into_iter()
+        .filter(|pr| pull_request_terminated(pr, terminal_state))
         .map(|pr| pull_request_duration(pr, terminal_state))
         .map(|timedelta| timedelta.num_seconds())
         .map(|secs| secs as f64 / SECONDS_PER_DAY as f64)
         .collect();
+    durations.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    durations
+}
+
+/// Returns the median number of days a PR takes to terminate.
+///
+/// Note that non-terminated PRs (i.e., PRs that are still open) are ignored as
+/// part of this calculation.
+pub fn median_pr_duration(
+    user: &str,
+    pull_requests: &Vec<PrRecord>,
+    terminal_state: PullRequestTerminatingState,
+) -> Option<f64> {
+    let durations: Vec<f64> = sorted_pr_durations(user, pull_requests, terminal_state);
+    let n: usize = durations.len();
+
+    match n {
+        0 => None,
+        _ if n % 2 == 0 => Some((durations[n / 2 - 1] + durations[n / 2]) / 2.0),
+        _ => Some(durations[n / 2]),
+    }
+}
+
+/// Returns the `p`th percentile (0.0..=100.0) of `user`'s PR durations, in days.
+///
+/// Uses linear interpolation between the two nearest ranks, which is the same
+/// convention as numpy's default `percentile` implementation. Returns `None`
+/// if `user` has no terminated PRs.
+pub fn percentile_pr_duration(
+    user: &str,
+    pull_requests: &Vec<PrRecord>,
+    terminal_state: PullRequestTerminatingState,
+    p: f64,
+) -> Option<f64> {
+    let durations: Vec<f64> = sorted_pr_durations(user, pull_requests, terminal_state);
     let n: usize = durations.len();
 
     match n {
         0 => None,
         1 => Some(durations[0]),
-        _ => Some(if n % 2 == 0 {
-            (durations[n / 2] + durations[(n / 2) + 1]) / 2.0
-        } else {
-            durations[(n + 1) / 2]
-        }),
+        _ => {
+            let rank: f64 = (p / 100.0) * (n - 1) as f64;
+            let lower: usize = rank.floor() as usize;
+            let upper: usize = rank.ceil() as usize;
+            let frac: f64 = rank - lower as f64;
+
+            Some(durations[lower] + (durations[upper] - durations[lower]) * frac)
+        }
+    }
+}
+
+/// Returns the 50th percentile (median) of `user`'s PR durations, in days.
+pub fn p50_pr_duration(
+    user: &str,
+    pull_requests: &Vec<PrRecord>,
+    terminal_state: PullRequestTerminatingState,
+) -> Option<f64> {
+    percentile_pr_duration(user, pull_requests, terminal_state, 50.0)
+}
+
+/// Returns the 90th percentile of `user`'s PR durations, in days.
+pub fn p90_pr_duration(
+    user: &str,
+    pull_requests: &Vec<PrRecord>,
+    terminal_state: PullRequestTerminatingState,
+) -> Option<f64> {
+    percentile_pr_duration(user, pull_requests, terminal_state, 90.0)
+}
+
+/// Returns the 99th percentile of `user`'s PR durations, in days.
+pub fn p99_pr_duration(
+    user: &str,
+    pull_requests: &Vec<PrRecord>,
+    terminal_state: PullRequestTerminatingState,
+) -> Option<f64> {
+    percentile_pr_duration(user, pull_requests, terminal_state, 99.0)
+}
+
+/// Default bucket width (in days) used when rendering a [`DurationHistogram`]
+/// for the CLI's `--histogram` report.
+pub const DEFAULT_HISTOGRAM_BUCKET_WIDTH_DAYS: u32 = 7;
+
+/// Default number of buckets used when rendering a [`DurationHistogram`] for
+/// the CLI's `--histogram` report.
+pub const DEFAULT_HISTOGRAM_NUM_BUCKETS: usize = 8;
+
+/// A histogram of PR durations, bucketed into fixed-width bins of
+/// `bucket_width_days` days each.
+///
+/// The final bucket is unbounded above, so a PR that ran for far longer than
+/// anything else doesn't force every other bucket to be near-empty.
+#[derive(Clone, Debug)]
+pub struct DurationHistogram {
+    /// Width of each bucket (except the last), in days.
+    pub bucket_width_days: u32,
+    /// `counts[i]` is the number of PRs whose duration falls in
+    /// `[i * bucket_width_days, (i + 1) * bucket_width_days)`, except for the
+    /// last bucket, which also includes everything beyond it.
+    pub counts: Vec<usize>,
+}
+
+impl DurationHistogram {
+    /// Buckets `user`'s PR durations into `num_buckets` bins of `bucket_width_days` each.
+    pub fn new(
+        user: &str,
+        pull_requests: &Vec<PrRecord>,
+        terminal_state: PullRequestTerminatingState,
+        bucket_width_days: u32,
+        num_buckets: usize,
+    ) -> Self {
+        let durations: Vec<f64> = sorted_pr_durations(user, pull_requests, terminal_state);
+        let mut counts: Vec<usize> = vec![0; num_buckets];
+
+        for duration in durations {
+            let bucket: usize = ((duration / bucket_width_days as f64) as usize).min(num_buckets - 1);
+            counts[bucket] += 1;
+        }
+
+        Self {
+            bucket_width_days,
+            counts,
+        }
+    }
+
+    /// Renders this histogram as an ASCII bar chart, one line per bucket.
+    pub fn render_ascii(&self) -> String {
+        let max_count: usize = self.counts.iter().copied().max().unwrap_or(0);
+        let mut report: String = String::new();
+
+        for (i, &count) in self.counts.iter().enumerate() {
+            let label: String = if i + 1 == self.counts.len() {
+                format!("{}+ days", i as u32 * self.bucket_width_days)
+            } else {
+                format!(
+                    "{}-{} days",
+                    i as u32 * self.bucket_width_days,
+                    (i + 1) as u32 * self.bucket_width_days
+                )
+            };
+            let bar_width: usize = if max_count == 0 {
+                0
+            } else {
+                (count * 40) / max_count
+            };
+
+            report += format!(
+                "{:>12} | {} {}\n",
+                label,
+                "#".repeat(bar_width),
+                count
+            )
+            .as_str();
+        }
+
+        report
     }
 }
 
@@ -177,10 +328,10 @@ pub fn median_pr_duration(
 #[allow(dead_code)]
 pub fn mean_net_change(
     user: &str,
-    pull_requests: &Vec<PullRequest>,
+    pull_requests: &Vec<PrRecord>,
     terminal_state: PullRequestTerminatingState,
 ) -> Option<f64> {
-    let users_prs: Vec<PullRequest> = pull_requests_by_author(user, &pull_requests);
+    let users_prs: Vec<PrRecord> = pull_requests_by_author(user, &pull_requests);
 
     if users_prs.is_empty() {
         None
@@ -199,6 +350,104 @@ pub fn mean_net_change(
 }
 
 /// Returns the number of PRs in `pull_requests` authored by `user`
-pub fn total_pull_requests(user: &str, pull_requests: &Vec<PullRequest>) -> usize {
+pub fn total_pull_requests(user: &str, pull_requests: &Vec<PrRecord>) -> usize {
     pull_requests_by_author(user, pull_requests).len()
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    /// Builds a merged PR authored by `alice` that took `days_to_merge` days
+    /// to complete, starting from a fixed epoch so durations are deterministic.
+    fn merged_pr(days_to_merge: i64) -> PrRecord {
+        let created_at = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+
+        PrRecord {
+            author: Some("alice".to_string()),
+            created_at: Some(created_at),
+            merged_at: Some(created_at + Duration::days(days_to_merge)),
+            closed_at: None,
+            additions: None,
+            deletions: None,
+        }
+    }
+
+    /// An open (never terminated) PR, which `sorted_pr_durations` should exclude.
+    fn open_pr(days_since_created: i64) -> PrRecord {
+        let created_at = Utc::now() - Duration::days(days_since_created);
+
+        PrRecord {
+            author: Some("alice".to_string()),
+            created_at: Some(created_at),
+            merged_at: None,
+            closed_at: None,
+            additions: None,
+            deletions: None,
+        }
+    }
+
+    #[test]
+    fn median_pr_duration_odd_number_of_prs() {
+        let prs = vec![merged_pr(1), merged_pr(2), merged_pr(3)];
+
+        assert_eq!(
+            median_pr_duration("alice", &prs, PullRequestTerminatingState::Merged),
+            Some(2.0)
+        );
+    }
+
+    #[test]
+    fn median_pr_duration_even_number_of_prs() {
+        let prs = vec![merged_pr(1), merged_pr(2), merged_pr(3), merged_pr(4)];
+
+        assert_eq!(
+            median_pr_duration("alice", &prs, PullRequestTerminatingState::Merged),
+            Some(2.5)
+        );
+    }
+
+    #[test]
+    fn median_and_percentiles_ignore_open_prs() {
+        let prs = vec![merged_pr(1), merged_pr(2), merged_pr(3), open_pr(365)];
+
+        assert_eq!(
+            median_pr_duration("alice", &prs, PullRequestTerminatingState::Merged),
+            Some(2.0)
+        );
+        assert_eq!(
+            p50_pr_duration("alice", &prs, PullRequestTerminatingState::Merged),
+            Some(2.0)
+        );
+    }
+
+    #[test]
+    fn p50_pr_duration_matches_median() {
+        let prs = vec![merged_pr(1), merged_pr(2), merged_pr(3), merged_pr(4)];
+
+        assert_eq!(
+            p50_pr_duration("alice", &prs, PullRequestTerminatingState::Merged),
+            median_pr_duration("alice", &prs, PullRequestTerminatingState::Merged)
+        );
+    }
+
+    #[test]
+    fn p90_pr_duration_interpolates_between_nearest_ranks() {
+        // Sorted durations: [0, 10, 20, 30, 40]. rank = 0.9 * 4 = 3.6, so the
+        // p90 falls 60% of the way between durations[3] (30) and durations[4] (40).
+        let prs = vec![
+            merged_pr(0),
+            merged_pr(10),
+            merged_pr(20),
+            merged_pr(30),
+            merged_pr(40),
+        ];
+
+        assert_eq!(
+            percentile_pr_duration("alice", &prs, PullRequestTerminatingState::Merged, 90.0),
+            Some(36.0)
+        );
+    }
+}
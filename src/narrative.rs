@@ -0,0 +1,115 @@
+//! Optional LLM-generated narrative summaries of a contributor's metrics.
+//!
+//! Gated behind the `narrative` feature: this calls out to an
+//! OpenAI-compatible chat completions endpoint, which is extra weight
+//! (`reqwest` is already a dependency, but it's still a network call on the
+//! default code path) that most users of `ghprod` don't need. It's purely
+//! additive -- callers fall back to the plain `metrics`/`output` report if
+//! no API key is configured or the request fails for any reason.
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::output::ContributorMetrics;
+
+/// Default endpoint for an OpenAI-compatible chat completions API.
+pub const DEFAULT_NARRATIVE_ENDPOINT: &str = "https://api.openai.com/v1/chat/completions";
+
+/// Model to request from the chat completions endpoint.
+pub const DEFAULT_NARRATIVE_MODEL: &str = "gpt-4o-mini";
+
+#[derive(Serialize)]
+struct ChatCompletionRequest<'a> {
+    model: &'a str,
+    messages: Vec<ChatMessage>,
+}
+
+#[derive(Serialize)]
+struct ChatMessage {
+    role: &'static str,
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct ChatCompletionResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatResponseMessage,
+}
+
+#[derive(Deserialize)]
+struct ChatResponseMessage {
+    content: String,
+}
+
+/// Builds the prompt fed to the chat completions endpoint for `user`.
+fn build_prompt(user: &str, metrics: &ContributorMetrics) -> String {
+    format!(
+        "Write a short (2-3 sentence), factual performance summary for the GitHub contributor \
+         \"{user}\" based only on these metrics -- don't speculate beyond them: {} PRs completed; \
+         mean PR duration {} days; median PR duration {} days; p90 PR duration {} days; p99 PR \
+         duration {} days; mean net code change {} lines.",
+        metrics.num_prs,
+        metrics
+            .mean_pr_duration
+            .map_or("unknown".to_string(), |v| format!("{:.1}", v)),
+        metrics
+            .median_pr_duration
+            .map_or("unknown".to_string(), |v| format!("{:.1}", v)),
+        metrics
+            .p90_pr_duration
+            .map_or("unknown".to_string(), |v| format!("{:.1}", v)),
+        metrics
+            .p99_pr_duration
+            .map_or("unknown".to_string(), |v| format!("{:.1}", v)),
+        metrics
+            .mean_net_change
+            .map_or("unknown".to_string(), |v| format!("{:.1}", v)),
+    )
+}
+
+/// Requests a short narrative summary of `metrics` from an OpenAI-compatible
+/// chat completions endpoint at `endpoint`, authenticated with `api_key`.
+///
+/// Returns `None` (rather than an error) on any failure so that narrative
+/// mode stays purely additive -- callers should fall back to the plain
+/// report in that case.
+pub async fn narrative_summary(
+    user: &str,
+    metrics: &ContributorMetrics,
+    endpoint: &str,
+    api_key: &str,
+) -> Option<String> {
+    let request_body = ChatCompletionRequest {
+        model: DEFAULT_NARRATIVE_MODEL,
+        messages: vec![ChatMessage {
+            role: "user",
+            content: build_prompt(user, metrics),
+        }],
+    };
+
+    let response = match reqwest::Client::new()
+        .post(endpoint)
+        .bearer_auth(api_key)
+        .json(&request_body)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+    {
+        Ok(response) => response,
+        Err(e) => {
+            warn!("Narrative request failed, falling back to the plain report: {}", e);
+            return None;
+        }
+    };
+
+    match response.json::<ChatCompletionResponse>().await {
+        Ok(body) => body.choices.into_iter().next().map(|choice| choice.message.content),
+        Err(e) => {
+            warn!("Failed to parse narrative response, falling back to the plain report: {}", e);
+            None
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! Rendering of reports in whichever format `--format` selected.
+use prettytable::{color, Attr, Cell, Row, Table};
+use serde::Serialize;
+
+use crate::cli::OutputFormat;
+
+/// A single contributor's computed metrics, ready to render in any format.
+#[derive(Clone, Debug, Serialize)]
+pub struct ContributorMetrics {
+    pub user: String,
+    pub num_prs: usize,
+    pub mean_pr_duration: Option<f64>,
+    pub median_pr_duration: Option<f64>,
+    pub p90_pr_duration: Option<f64>,
+    pub p99_pr_duration: Option<f64>,
+    pub mean_net_change: Option<f64>,
+}
+
+fn optional_cell(value: Option<f64>) -> Cell {
+    match value {
+        Some(value) => Cell::new(&format!("{:.2}", value)),
+        None => Cell::new("-"),
+    }
+}
+
+/// Colors net change green when the codebase grew and red when it shrank.
+fn net_change_cell(net_change: Option<f64>) -> Cell {
+    match net_change {
+        Some(net_change) if net_change.is_sign_negative() => {
+            Cell::new(&format!("{:.2}", net_change)).with_style(Attr::ForegroundColor(color::RED))
+        }
+        Some(net_change) => Cell::new(&format!("{:.2}", net_change))
+            .with_style(Attr::ForegroundColor(color::GREEN)),
+        None => Cell::new("-"),
+    }
+}
+
+/// Renders a leaderboard of `contributors` in `format` and prints it to stdout.
+pub fn render_leaderboard(contributors: &[ContributorMetrics], format: OutputFormat) {
+    match format {
+        OutputFormat::Json => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(contributors)
+                    .expect("ContributorMetrics is always serialisable")
+            );
+        }
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.set_titles(Row::new(vec![
+                Cell::new("user"),
+                Cell::new("# PRs"),
+                Cell::new("mean duration (days)"),
+                Cell::new("median duration (days)"),
+                Cell::new("p90 duration (days)"),
+                Cell::new("p99 duration (days)"),
+                Cell::new("mean net change"),
+            ]));
+
+            for contributor in contributors {
+                table.add_row(Row::new(vec![
+                    Cell::new(&contributor.user),
+                    Cell::new(&contributor.num_prs.to_string()),
+                    optional_cell(contributor.mean_pr_duration),
+                    optional_cell(contributor.median_pr_duration),
+                    optional_cell(contributor.p90_pr_duration),
+                    optional_cell(contributor.p99_pr_duration),
+                    net_change_cell(contributor.mean_net_change),
+                ]));
+            }
+
+            table.printstd();
+        }
+        OutputFormat::Plain => {
+            for contributor in contributors {
+                println!(
+                    "{}: {} PRs, mean {} days, median {} days, p90 {} days, p99 {} days, net change {}",
+                    contributor.user,
+                    contributor.num_prs,
+                    contributor
+                        .mean_pr_duration
+                        .map_or("(null)".to_string(), |v| v.to_string()),
+                    contributor
+                        .median_pr_duration
+                        .map_or("(null)".to_string(), |v| v.to_string()),
+                    contributor
+                        .p90_pr_duration
+                        .map_or("(null)".to_string(), |v| v.to_string()),
+                    contributor
+                        .p99_pr_duration
+                        .map_or("(null)".to_string(), |v| v.to_string()),
+                    contributor
+                        .mean_net_change
+                        .map_or("(null)".to_string(), |v| v.to_string()),
+                );
+            }
+        }
+    }
+}
+
+/// Renders a single scalar metric value in `format` and prints it to stdout.
+pub fn render_metric(metric_name: &str, value: Option<f64>, format: OutputFormat) {
+    match format {
+        OutputFormat::Json => {
+            let payload = serde_json::json!({ "metric": metric_name, "value": value });
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&payload).expect("payload is always serialisable")
+            );
+        }
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.set_titles(Row::new(vec![Cell::new("metric"), Cell::new("value")]));
+            table.add_row(Row::new(vec![Cell::new(metric_name), optional_cell(value)]));
+            table.printstd();
+        }
+        OutputFormat::Plain => match value {
+            Some(value) => println!("{}", value),
+            None => println!("(null)"),
+        },
+    }
+}